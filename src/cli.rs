@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+use crate::config::Config;
+use crate::daemon;
+
+/// A pomodoro timer daemon and its control client.
+#[derive(Parser, Debug)]
+#[clap(name = "pomod")]
+pub struct Cli {
+  /// Path to the daemon's control socket.
+  #[clap(long, global = true)]
+  pub socket: Option<PathBuf>,
+
+  #[clap(subcommand)]
+  pub command: Cmd,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Cmd {
+  /// Starts the daemon and its poll loop.
+  Daemon {
+    /// Overrides the configured pomodoro length, e.g. `25m`.
+    #[clap(long, parse(try_from_str = parse_duration))]
+    work: Option<Duration>,
+
+    /// Overrides the configured short break length, e.g. `5m`.
+    #[clap(long = "short-break", parse(try_from_str = parse_duration))]
+    short_break: Option<Duration>,
+
+    /// Overrides the configured long break length, e.g. `30m`.
+    #[clap(long = "long-break", parse(try_from_str = parse_duration))]
+    long_break: Option<Duration>,
+  },
+  /// Starts or pauses the timer.
+  Toggle,
+  /// Resets the timer to a fresh state.
+  Reset,
+  /// Skips to the next phase.
+  Skip,
+  /// Prints the current status line.
+  Status,
+  /// Confirms rolling over into the next phase, when waiting.
+  Confirm,
+  /// Declines rolling over into the next phase, stopping the cycle.
+  Decline,
+  /// Prints aggregate completed-pomodoro stats.
+  Stats,
+}
+
+fn parse_duration(raw: &str) -> Result<Duration, String> {
+  humantime::parse_duration(raw).map_err(|err| err.to_string())
+}
+
+impl Cli {
+  pub fn socket_path(&self) -> PathBuf {
+    self
+      .socket
+      .clone()
+      .unwrap_or_else(daemon::default_socket_path)
+  }
+}
+
+impl Cmd {
+  /// Applies any `Daemon` duration overrides onto a loaded `Config`.
+  pub fn apply_overrides(&self, config: &mut Config) {
+    if let Cmd::Daemon {
+      work,
+      short_break,
+      long_break,
+    } = self
+    {
+      if let Some(work) = work {
+        config.work_time = *work;
+      }
+      if let Some(short_break) = short_break {
+        config.short_break = *short_break;
+      }
+      if let Some(long_break) = long_break {
+        config.long_break = *long_break;
+      }
+    }
+  }
+}
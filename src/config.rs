@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_FILE_NAME: &str = "settings.toml";
+
+/// User-configurable timer durations and miscellany, loaded from the
+/// platform config directory (e.g. `~/.config/pomod/settings.toml` on
+/// Linux). Durations are written and read as human-friendly strings like
+/// `25m` or `1h30m` (see `crate::duration`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+  #[serde(with = "crate::duration")]
+  pub work_time: Duration,
+  #[serde(with = "crate::duration")]
+  pub short_break: Duration,
+  #[serde(with = "crate::duration")]
+  pub long_break: Duration,
+  pub break_cycle: u8,
+  pub sound_file: Option<PathBuf>,
+  /// When set, the timer waits for a `Confirm`/`Decline` command between
+  /// phases instead of rolling over on its own.
+  #[serde(default)]
+  pub confirm_between_states: bool,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Config {
+      work_time: Duration::from_secs(25 * 60),
+      short_break: Duration::from_secs(5 * 60),
+      long_break: Duration::from_secs(30 * 60),
+      break_cycle: 4,
+      sound_file: None,
+      confirm_between_states: false,
+    }
+  }
+}
+
+impl Config {
+  /// Loads the config from the platform config dir, writing out a default
+  /// `settings.toml` on first run.
+  pub fn load() -> Self {
+    let path = Self::path();
+
+    let config = match fs::read_to_string(&path) {
+      Ok(contents) => toml::from_str(&contents).unwrap_or_else(|err| {
+        eprintln!(
+          "pomod: failed to parse {}: {}, falling back to defaults",
+          path.display(),
+          err
+        );
+        Config::default()
+      }),
+      Err(_) => {
+        let config = Config::default();
+        config.write(&path);
+        config
+      }
+    };
+
+    config.validated(&path)
+  }
+
+  /// Guards against settings that would make the timer panic at runtime
+  /// (e.g. a hand-edited `break_cycle = 0`), repairing just the offending
+  /// field rather than discarding the rest of the user's config.
+  fn validated(mut self, path: &Path) -> Self {
+    if self.break_cycle == 0 {
+      eprintln!(
+        "pomod: {} has break_cycle = 0, which is invalid; using the default of {} instead",
+        path.display(),
+        Config::default().break_cycle
+      );
+      self.break_cycle = Config::default().break_cycle;
+    }
+
+    self
+  }
+
+  fn write(&self, path: &Path) {
+    if let Some(parent) = path.parent() {
+      let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(contents) = toml::to_string_pretty(self) {
+      let _ = fs::write(path, contents);
+    }
+  }
+
+  fn path() -> PathBuf {
+    ProjectDirs::from("", "", "pomod")
+      .map(|dirs| dirs.config_dir().join(CONFIG_FILE_NAME))
+      .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME))
+  }
+}
@@ -0,0 +1,196 @@
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::stats::{self, Stats};
+use crate::timer::{minutes, seconds, Timer, TimerState};
+
+/// A request sent by a client over the control socket.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Command {
+  Toggle,
+  Reset,
+  Skip,
+  Status,
+  /// Confirms rolling over into the next phase, when `waiting_confirmation`.
+  Confirm,
+  /// Declines rolling over into the next phase, stopping the cycle.
+  Decline,
+  /// Reports aggregate completed-pomodoro stats.
+  Stats,
+}
+
+/// The daemon's reply to a `Command`, describing the timer at the moment
+/// the command was handled.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Answer {
+  pub state: TimerState,
+  pub running: bool,
+  pub minutes: u64,
+  pub seconds: u64,
+  pub break_counter: u8,
+  /// Set when the timer is paused waiting for a `Confirm`/`Decline`.
+  pub waiting_confirmation: bool,
+  /// How long the timer has been waiting for confirmation, if at all.
+  pub waiting_seconds: Option<u64>,
+}
+
+impl Answer {
+  /// Renders the answer as the single-line status string used for bar
+  /// integration (e.g. `i3status`/`polybar`).
+  pub fn status_line(&self) -> String {
+    let suffix = if self.waiting_confirmation {
+      " continue? (y/n)"
+    } else {
+      ""
+    };
+
+    format!(
+      "{} {:02}:{:02}{}",
+      self.state.pomicon(),
+      self.minutes,
+      self.seconds,
+      suffix
+    )
+  }
+
+  fn from_timer(timer: &Timer) -> Self {
+    let remaining = timer.remaining_time.unwrap_or_else(|| Duration::new(0, 0));
+    Answer {
+      state: timer.state,
+      running: timer.running,
+      minutes: minutes(&remaining),
+      seconds: seconds(&remaining),
+      break_counter: timer.break_counter,
+      waiting_confirmation: timer.paused,
+      waiting_seconds: timer.waiting_duration().map(|d| d.as_secs()),
+    }
+  }
+}
+
+/// The daemon's reply, shaped differently depending on the `Command` that
+/// was sent.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Reply {
+  Status(Answer),
+  Stats(Stats),
+}
+
+/// Returns the default control socket path, under the platform's runtime
+/// (or temp) directory.
+pub fn default_socket_path() -> PathBuf {
+  directories::ProjectDirs::from("", "", "pomod")
+    .and_then(|dirs| dirs.runtime_dir().map(|dir| dir.join("pomod.sock")))
+    .unwrap_or_else(|| std::env::temp_dir().join("pomod.sock"))
+}
+
+/// Runs the daemon: owns the `Timer`, polls it on a background thread, and
+/// serves `Command`/`Answer` pairs to clients connecting to `socket_path`.
+pub fn run(
+  socket_path: &Path,
+  config: Config,
+  on_state_change: impl FnMut(TimerState) + Send + 'static,
+) -> io::Result<()> {
+  let _ = std::fs::remove_file(socket_path);
+  if let Some(parent) = socket_path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  let listener = UnixListener::bind(socket_path)?;
+
+  let mut timer = Timer::new(config);
+  timer.on_state_change(on_state_change);
+  let timer = Arc::new(Mutex::new(timer));
+
+  {
+    let timer = Arc::clone(&timer);
+    thread::spawn(move || loop {
+      timer.lock().unwrap().poll();
+      thread::sleep(Duration::from_millis(500));
+    });
+  }
+
+  for stream in listener.incoming() {
+    let stream = stream?;
+    let timer = Arc::clone(&timer);
+    thread::spawn(move || {
+      if let Err(err) = handle_client(stream, &timer) {
+        eprintln!("pomod: client error: {}", err);
+      }
+    });
+  }
+
+  Ok(())
+}
+
+fn handle_client(mut stream: UnixStream, timer: &Arc<Mutex<Timer>>) -> io::Result<()> {
+  let command = read_message(&mut stream)?;
+
+  if let Command::Stats = command {
+    let reply = Reply::Stats(stats::stats()?);
+    return write_message(&mut stream, &reply);
+  }
+
+  let reply = {
+    let mut timer = timer.lock().unwrap();
+    match command {
+      Command::Toggle => timer.toggle(),
+      Command::Reset => {
+        let config = timer.config.clone();
+        timer.reset(config);
+      }
+      Command::Skip => timer.skip(),
+      Command::Confirm => timer.confirm(),
+      Command::Decline => timer.decline(),
+      Command::Status => {}
+      Command::Stats => unreachable!("handled above"),
+    }
+    Reply::Status(Answer::from_timer(&timer))
+  };
+
+  write_message(&mut stream, &reply)
+}
+
+/// Messages on this socket are small CBOR-encoded enums; anything near
+/// this size is already a malformed or hostile length prefix.
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+fn read_message<T: serde::de::DeserializeOwned>(stream: &mut UnixStream) -> io::Result<T> {
+  let mut len_buf = [0u8; 4];
+  stream.read_exact(&mut len_buf)?;
+  let len = u32::from_be_bytes(len_buf) as usize;
+
+  if len > MAX_MESSAGE_LEN {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!("message length {} exceeds max of {}", len, MAX_MESSAGE_LEN),
+    ));
+  }
+
+  let mut buf = vec![0u8; len];
+  stream.read_exact(&mut buf)?;
+
+  serde_cbor::from_slice(&buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn write_message<T: Serialize>(stream: &mut UnixStream, message: &T) -> io::Result<()> {
+  let bytes =
+    serde_cbor::to_vec(message).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+  stream.write_all(&(bytes.len() as u32).to_be_bytes())?;
+  stream.write_all(&bytes)?;
+  Ok(())
+}
+
+/// Sends `command` to the daemon listening on `socket_path` and returns its
+/// `Reply`.
+pub fn send_command(socket_path: &Path, command: &Command) -> io::Result<Reply> {
+  let mut stream = UnixStream::connect(socket_path)?;
+  write_message(&mut stream, command)?;
+  read_message(&mut stream)
+}
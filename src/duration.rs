@@ -0,0 +1,22 @@
+//! Serde (de)serialization of `Duration` as human-friendly strings like
+//! `25m` or `1h30m`, via the `humantime` crate. Used as
+//! `#[serde(with = "crate::duration")]` on `Config` fields.
+
+use std::time::Duration;
+
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let raw = String::deserialize(deserializer)?;
+  humantime::parse_duration(&raw).map_err(DeError::custom)
+}
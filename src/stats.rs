@@ -0,0 +1,196 @@
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+const LOG_FILE_NAME: &str = "sessions.log";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A single completed pomodoro phase, appended to the session log as a
+/// line of JSON.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+  pub started_at: u64,
+  pub duration_secs: u64,
+  pub break_counter: u8,
+}
+
+impl Entry {
+  pub fn new(started_at: SystemTime, duration: Duration, break_counter: u8) -> Self {
+    Entry {
+      started_at: started_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs(),
+      duration_secs: duration.as_secs(),
+      break_counter,
+    }
+  }
+}
+
+/// Aggregate stats derived from the session log.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Stats {
+  pub completed_today: u64,
+  pub total_focus_seconds: u64,
+  pub current_streak_days: u64,
+}
+
+impl Stats {
+  pub fn summary_line(&self) -> String {
+    format!(
+      "{} pomodoros today, {}m focused total, {}-day streak",
+      self.completed_today,
+      self.total_focus_seconds / 60,
+      self.current_streak_days
+    )
+  }
+}
+
+fn log_path() -> PathBuf {
+  ProjectDirs::from("", "", "pomod")
+    .map(|dirs| dirs.data_dir().join(LOG_FILE_NAME))
+    .unwrap_or_else(|| PathBuf::from(LOG_FILE_NAME))
+}
+
+/// Appends a completed pomodoro to the session log.
+pub fn record(entry: &Entry) -> io::Result<()> {
+  let path = log_path();
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+  let line = serde_json::to_string(entry)
+    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+  writeln!(file, "{}", line)
+}
+
+/// Reads the session log and computes aggregate stats.
+pub fn stats() -> io::Result<Stats> {
+  let entries = match fs::File::open(log_path()) {
+    Ok(file) => read_entries(file)?,
+    Err(_) => Vec::new(),
+  };
+
+  Ok(aggregate(&entries))
+}
+
+fn read_entries(file: fs::File) -> io::Result<Vec<Entry>> {
+  BufReader::new(file)
+    .lines()
+    .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+    .map(|line| {
+      let line = line?;
+      serde_json::from_str(&line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    })
+    .collect()
+}
+
+fn day_number(unix_secs: u64) -> u64 {
+  unix_secs / SECONDS_PER_DAY
+}
+
+fn today() -> u64 {
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs();
+  day_number(now)
+}
+
+fn aggregate(entries: &[Entry]) -> Stats {
+  let today = today();
+
+  let completed_today = entries
+    .iter()
+    .filter(|entry| day_number(entry.started_at) == today)
+    .count() as u64;
+  let total_focus_seconds = entries.iter().map(|entry| entry.duration_secs).sum();
+
+  Stats {
+    completed_today,
+    total_focus_seconds,
+    current_streak_days: current_streak(entries, today),
+  }
+}
+
+/// Counts consecutive days (ending today) with at least one completed
+/// pomodoro. Today is allowed to have none yet without breaking the streak.
+fn current_streak(entries: &[Entry], today: u64) -> u64 {
+  let days: BTreeSet<u64> = entries.iter().map(|entry| day_number(entry.started_at)).collect();
+
+  let mut day = today;
+  if !days.contains(&day) {
+    match day.checked_sub(1) {
+      Some(previous) => day = previous,
+      None => return 0,
+    }
+  }
+
+  let mut streak = 0;
+  while days.contains(&day) {
+    streak += 1;
+    match day.checked_sub(1) {
+      Some(previous) => day = previous,
+      None => break,
+    }
+  }
+
+  streak
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(day: u64, duration_secs: u64) -> Entry {
+    Entry {
+      started_at: day * SECONDS_PER_DAY,
+      duration_secs,
+      break_counter: 0,
+    }
+  }
+
+  #[test]
+  fn current_streak_counts_consecutive_days_ending_today() {
+    let entries = vec![entry(8, 60), entry(9, 60), entry(10, 60)];
+    assert_eq!(current_streak(&entries, 10), 3);
+  }
+
+  #[test]
+  fn current_streak_allows_today_to_have_nothing_yet() {
+    let entries = vec![entry(8, 60), entry(9, 60)];
+    assert_eq!(current_streak(&entries, 10), 2);
+  }
+
+  #[test]
+  fn current_streak_breaks_on_a_gap() {
+    let entries = vec![entry(7, 60), entry(9, 60), entry(10, 60)];
+    assert_eq!(current_streak(&entries, 10), 2);
+  }
+
+  #[test]
+  fn current_streak_is_zero_with_no_entries() {
+    assert_eq!(current_streak(&[], 10), 0);
+  }
+
+  #[test]
+  fn aggregate_sums_focus_time_and_counts_todays_entries() {
+    let today = today();
+    let entries = vec![
+      entry(today, 300),
+      entry(today, 600),
+      entry(today - 1, 900),
+    ];
+
+    let stats = aggregate(&entries);
+    assert_eq!(stats.completed_today, 2);
+    assert_eq!(stats.total_focus_seconds, 1800);
+    assert_eq!(stats.current_streak_days, 2);
+  }
+}
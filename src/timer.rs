@@ -0,0 +1,366 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::stats;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimerState {
+  None,
+  Pomodoro,
+  ShortBreak,
+  LongBreak,
+}
+
+impl TimerState {
+  pub fn time(self, config: &Config) -> Duration {
+    use TimerState::*;
+
+    match self {
+      None | Pomodoro => config.work_time,
+      ShortBreak => config.short_break,
+      LongBreak => config.long_break,
+    }
+  }
+
+  pub fn pomicon(self) -> String {
+    use TimerState::*;
+
+    String::from(match self {
+      None => "",
+      Pomodoro => "",
+      ShortBreak => "",
+      LongBreak => "",
+    })
+  }
+
+  fn next(&mut self, break_counter: &mut u8, config: &Config) {
+    use TimerState::*;
+
+    match self {
+      None => *self = Pomodoro,
+      Pomodoro => {
+        if *break_counter < config.break_cycle - 1 {
+          *self = ShortBreak;
+        } else {
+          *self = LongBreak;
+        }
+        *break_counter = (*break_counter + 1) % config.break_cycle;
+      }
+      ShortBreak | LongBreak => *self = Pomodoro,
+    }
+  }
+}
+
+pub struct Timer {
+  pub(crate) running: bool,
+  pub(crate) state: TimerState,
+  state_start_time: Option<Instant>,
+  pub(crate) remaining_time: Option<Duration>,
+  last_poll: Instant,
+  pub(crate) break_counter: u8,
+  state_change_callback: Option<Box<dyn FnMut(TimerState) + Send>>,
+  pub(crate) config: Config,
+  /// Set while waiting for the user to confirm rolling over into the next
+  /// phase (only used when `config.confirm_between_states` is set).
+  pub(crate) paused: bool,
+  pub(crate) wait_start: Option<Instant>,
+  /// Wall-clock time the current phase began, used to log completed
+  /// pomodoros with a real timestamp.
+  phase_started_at: Option<SystemTime>,
+  /// How long the just-finished phase actually ran, captured the moment it
+  /// hit zero and `paused` was set — so a `Confirm` that arrives later
+  /// doesn't count the confirmation wait itself as focused time.
+  paused_elapsed: Option<Duration>,
+}
+
+pub fn minutes(duration: &Duration) -> u64 {
+  duration.as_secs() / 60
+}
+
+pub fn seconds(duration: &Duration) -> u64 {
+  duration.as_secs() % 60
+}
+
+impl Timer {
+  pub fn new(config: Config) -> Self {
+    let remaining_time = Some(TimerState::None.time(&config));
+    Timer {
+      running: false,
+      state: TimerState::None,
+      state_start_time: None,
+      remaining_time,
+      last_poll: Instant::now(),
+      break_counter: 0,
+      state_change_callback: None,
+      config,
+      paused: false,
+      wait_start: None,
+      phase_started_at: None,
+      paused_elapsed: None,
+    }
+  }
+
+  /// Resets the timer to a fresh `None` state, keeping the given config.
+  pub fn reset(&mut self, config: Config) {
+    let callback = self.state_change_callback.take();
+    *self = Timer::new(config);
+    self.state_change_callback = callback;
+  }
+
+  pub fn start(&mut self) {
+    if !self.running {
+      if self.state_start_time.is_none() {
+        self.state_start_time = Some(Instant::now());
+        self.begin_next_state();
+      }
+      self.running = true;
+    }
+  }
+
+  pub fn stop(&mut self) {
+    if self.running {
+      self.running = false;
+    }
+  }
+
+  pub fn toggle(&mut self) {
+    if !self.running {
+      self.start();
+    } else {
+      self.stop();
+    }
+  }
+
+  /// Advances to the next phase, as a genuine completion (natural expiry
+  /// or a user confirmation) — logs a completed pomodoro when leaving
+  /// `Pomodoro`. `skip()` bypasses this transition for forced advances.
+  pub fn begin_next_state(&mut self) {
+    self.advance(None);
+  }
+
+  /// Like `begin_next_state`, but `elapsed_override` (when given) is used
+  /// as the logged focus time instead of `now - phase_started_at` — needed
+  /// when the transition is delayed by a confirmation wait.
+  fn advance(&mut self, elapsed_override: Option<Duration>) {
+    let (was_pomodoro, break_counter_before, started_at) = self.transition();
+
+    if was_pomodoro {
+      if let Some(started_at) = started_at {
+        let elapsed = elapsed_override.unwrap_or_else(|| {
+          SystemTime::now()
+            .duration_since(started_at)
+            .unwrap_or(self.config.work_time)
+        });
+        self.record_completed_pomodoro(started_at, elapsed, break_counter_before);
+      }
+    }
+  }
+
+  /// Transitions `state`/`remaining_time` to the next phase, without
+  /// recording a completed pomodoro.
+  fn transition(&mut self) -> (bool, u8, Option<SystemTime>) {
+    let was_pomodoro = self.state == TimerState::Pomodoro;
+    let break_counter_before = self.break_counter;
+    let started_at = self.phase_started_at;
+
+    self.state.next(&mut self.break_counter, &self.config);
+    self.remaining_time = Some(self.state.time(&self.config));
+    self.phase_started_at = Some(SystemTime::now());
+
+    (was_pomodoro, break_counter_before, started_at)
+  }
+
+  fn record_completed_pomodoro(
+    &self,
+    started_at: SystemTime,
+    elapsed: Duration,
+    break_counter_before: u8,
+  ) {
+    let entry = stats::Entry::new(started_at, elapsed, break_counter_before);
+    if let Err(err) = stats::record(&entry) {
+      eprintln!("pomod: failed to record completed pomodoro: {}", err);
+    }
+  }
+
+  pub fn on_state_change<F>(&mut self, callback: F)
+  where
+    F: FnMut(TimerState) + Send + 'static,
+  {
+    self.state_change_callback = Some(Box::new(callback));
+  }
+
+  fn fire_callback(&mut self) {
+    if let Some(callback) = self.state_change_callback.as_mut() {
+      callback(self.state);
+    }
+  }
+
+  /// Confirms rolling over into the next phase after a `paused`
+  /// confirmation wait.
+  pub fn confirm(&mut self) {
+    if self.paused {
+      self.paused = false;
+      self.wait_start = None;
+      let elapsed = self.paused_elapsed.take();
+      self.advance(elapsed);
+      self.fire_callback();
+    }
+  }
+
+  /// Skips to the next phase immediately, clearing any pending
+  /// confirmation wait (the `Skip` command should work regardless of
+  /// whether the timer is currently paused on one). Unlike
+  /// `begin_next_state`, this never logs a completed pomodoro — the phase
+  /// may have been cut short.
+  pub fn skip(&mut self) {
+    self.paused = false;
+    self.wait_start = None;
+    self.paused_elapsed = None;
+    self.transition();
+  }
+
+  /// Declines rolling over into the next phase, stopping the cycle.
+  /// Drives the timer back to a fresh pre-start state (rather than just
+  /// flipping `running`/`paused`) so a later `Toggle`/`start` actually
+  /// begins a new pomodoro instead of immediately re-pausing.
+  pub fn decline(&mut self) {
+    if self.paused {
+      self.paused = false;
+      self.wait_start = None;
+      self.paused_elapsed = None;
+      self.break_counter = 0;
+      self.state = TimerState::None;
+      self.remaining_time = Some(TimerState::None.time(&self.config));
+      self.state_start_time = None;
+      self.phase_started_at = None;
+      self.stop();
+    }
+  }
+
+  /// How long the timer has been waiting for confirmation, if `paused`.
+  pub fn waiting_duration(&self) -> Option<Duration> {
+    self.wait_start.map(|start| Instant::now() - start)
+  }
+
+  pub fn poll(&mut self) {
+    if self.running {
+      if self.paused {
+        // Waiting for the user to `confirm`/`decline`; nothing to do.
+      } else if self.remaining_time.is_none() {
+        if self.config.confirm_between_states {
+          self.paused = true;
+          self.wait_start = Some(Instant::now());
+          self.paused_elapsed = self.phase_started_at.map(|started_at| {
+            SystemTime::now()
+              .duration_since(started_at)
+              .unwrap_or(self.config.work_time)
+          });
+        } else {
+          self.begin_next_state();
+          self.fire_callback();
+        }
+      } else {
+        self.remaining_time = self
+          .remaining_time
+          .unwrap()
+          .checked_sub(Instant::now() - self.last_poll);
+      }
+    }
+    self.last_poll = Instant::now();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::thread::sleep;
+
+  use super::*;
+
+  fn confirm_config() -> Config {
+    Config {
+      work_time: Duration::from_millis(20),
+      short_break: Duration::from_millis(20),
+      long_break: Duration::from_millis(20),
+      break_cycle: 4,
+      sound_file: None,
+      confirm_between_states: true,
+    }
+  }
+
+  /// Runs the phase out and polls until the timer reports `paused`.
+  fn expire_and_pause(timer: &mut Timer) {
+    sleep(Duration::from_millis(50));
+    timer.poll();
+    timer.poll();
+    assert!(timer.paused, "timer should be waiting for confirmation");
+  }
+
+  #[test]
+  fn decline_resets_to_a_fresh_pre_start_state() {
+    let mut timer = Timer::new(confirm_config());
+    timer.start();
+    expire_and_pause(&mut timer);
+
+    timer.decline();
+    assert!(!timer.running);
+    assert!(!timer.paused);
+    assert_eq!(timer.state, TimerState::None);
+
+    // A later Toggle must actually run the next pomodoro, not immediately
+    // re-pause with zero time elapsed.
+    timer.toggle();
+    assert!(timer.running);
+    assert_eq!(timer.state, TimerState::Pomodoro);
+    assert!(!timer.paused);
+
+    expire_and_pause(&mut timer);
+  }
+
+  #[test]
+  fn skip_while_paused_does_not_get_stuck() {
+    let mut timer = Timer::new(confirm_config());
+    timer.start();
+    expire_and_pause(&mut timer);
+
+    timer.skip();
+    assert!(!timer.paused);
+    assert_eq!(timer.state, TimerState::ShortBreak);
+
+    // A previously-stuck timer would stay `paused` forever from here on.
+    timer.poll();
+    assert!(!timer.paused);
+  }
+
+  #[test]
+  fn skip_cuts_a_running_pomodoro_short() {
+    let mut timer = Timer::new(confirm_config());
+    timer.start();
+    sleep(Duration::from_millis(5));
+
+    timer.skip();
+    assert_eq!(timer.state, TimerState::ShortBreak);
+    // `skip` takes the non-recording `transition` path rather than
+    // `begin_next_state`, since the phase may have been cut short; the
+    // session log itself is not inspected here.
+  }
+
+  #[test]
+  fn confirm_logs_elapsed_time_captured_at_expiry_not_at_confirm() {
+    let mut timer = Timer::new(confirm_config());
+    timer.start();
+    expire_and_pause(&mut timer);
+
+    let elapsed_at_pause = timer
+      .paused_elapsed
+      .expect("should have captured elapsed time when the phase expired");
+
+    // Time passing while waiting for confirmation must not inflate the
+    // logged focus time.
+    sleep(Duration::from_millis(50));
+    timer.confirm();
+
+    assert!(elapsed_at_pause < Duration::from_millis(40));
+  }
+}